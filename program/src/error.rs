@@ -0,0 +1,55 @@
+use num_derive::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+use thiserror::Error;
+
+/// Errors that may be returned by the Dex program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum DexError {
+    #[error("This account is already initialized")]
+    AlreadyInitialized,
+    #[error("This account is not initialized")]
+    UninitializedAccount,
+    #[error("The account is not owned by the expected program")]
+    WrongOwner,
+    #[error("The user account has reached its maximum capacity for open orders")]
+    UserAccountFull,
+    #[error("The provided order index is invalid")]
+    InvalidOrderIndex,
+    #[error("No order was found matching the given criteria")]
+    OrderNotFound,
+    #[error("The order would have crossed the book, which is not allowed for a post-only order")]
+    WouldCross,
+    #[error("The order could not be filled in its entirety immediately, which is required for a fill-or-kill order")]
+    CouldNotFill,
+    #[error("The realized trade output is below the specified slippage bound")]
+    SlippageOutOfBounds,
+    #[error("The order's expiry has already elapsed, so it can no longer be placed")]
+    OrderExpired,
+    #[error("Shrinking the user account by a chunk would drop its capacity below its live order count")]
+    CannotShrinkUserAccount,
+}
+
+impl PrintProgramError for DexError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + num_traits::FromPrimitive,
+    {
+        msg!("{}", &self.to_string());
+    }
+}
+
+impl From<DexError> for ProgramError {
+    fn from(e: DexError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for DexError {
+    fn type_of() -> &'static str {
+        "DexError"
+    }
+}
@@ -0,0 +1,8 @@
+#![allow(clippy::too_many_arguments)]
+
+pub mod error;
+pub mod processor;
+pub mod state;
+pub mod utils;
+
+solana_program::declare_id!("DEXYosS6oEGvk8uCDayvwEZz4qEyDJRf9nFgYCaqPMTm");
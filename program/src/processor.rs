@@ -0,0 +1,61 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod claim_referral_fees;
+pub mod grow_user_account;
+pub mod new_order;
+pub mod prune;
+pub mod send_take;
+pub mod shrink_user_account;
+
+/// The SRM mint, used alongside [`MSRM_MINT`] to determine a user's fee tier from their held balance.
+pub const SRM_MINT: Pubkey = solana_program::pubkey!("SRMuApVNdxXokk5GT7XD5cUUgXMBCoAz2LHeuAoKWRt");
+/// The MSRM mint, holding any amount of which grants the top fee tier.
+pub const MSRM_MINT: Pubkey = solana_program::pubkey!("MSRMmGt3DyurdFCnv29qxCvX4Z6AbLjjPME6r7dMZU");
+/// Bitmask flagging a packed fee tier byte as belonging to a referred account.
+pub const REFERRAL_MASK: u8 = 1 << 7;
+
+#[allow(missing_docs)]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum DexInstruction {
+    /// Match a taker order against the book and settle the net proceeds directly to the
+    /// caller's SPL token wallets, without posting any remainder.
+    SendTake(send_take::Params),
+    /// Post a new order against the book, honoring its `OrderType` and expiry.
+    NewOrder(new_order::Params),
+    /// Permissionlessly prune expired resting orders from a user account.
+    Prune(prune::Params),
+    /// Grow a user account's backing storage by one [`grow_user_account::GROW_CHUNK_SIZE`] chunk.
+    GrowUserAccount(grow_user_account::Params),
+    /// Shrink a user account's backing storage by one chunk, reclaiming rent.
+    ShrinkUserAccount(shrink_user_account::Params),
+    /// Claim a referrer's accrued referral fees out of the market's quote vault.
+    ClaimReferralFees(claim_referral_fees::Params),
+}
+
+#[allow(missing_docs)]
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = DexInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        DexInstruction::SendTake(params) => send_take::process(program_id, accounts, params),
+        DexInstruction::NewOrder(params) => new_order::process(program_id, accounts, params),
+        DexInstruction::Prune(params) => prune::process(program_id, accounts, params),
+        DexInstruction::GrowUserAccount(params) => {
+            grow_user_account::process(program_id, accounts, params)
+        }
+        DexInstruction::ShrinkUserAccount(params) => {
+            shrink_user_account::process(program_id, accounts, params)
+        }
+        DexInstruction::ClaimReferralFees(params) => {
+            claim_referral_fees::process(program_id, accounts, params)
+        }
+    }
+}
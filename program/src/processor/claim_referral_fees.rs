@@ -0,0 +1,86 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::state::{DexState, UserAccount};
+
+#[allow(missing_docs)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct Params {}
+
+struct Accounts<'a, 'b: 'a> {
+    market: &'a AccountInfo<'b>,
+    market_signer: &'a AccountInfo<'b>,
+    quote_vault: &'a AccountInfo<'b>,
+    referrer_user_account: &'a AccountInfo<'b>,
+    referrer: &'a AccountInfo<'b>,
+    referrer_quote_wallet: &'a AccountInfo<'b>,
+    spl_token_program: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, 'b> {
+    fn parse(accounts: &'a [AccountInfo<'b>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let a = Self {
+            market: next_account_info(accounts_iter)?,
+            market_signer: next_account_info(accounts_iter)?,
+            quote_vault: next_account_info(accounts_iter)?,
+            referrer_user_account: next_account_info(accounts_iter)?,
+            referrer: next_account_info(accounts_iter)?,
+            referrer_quote_wallet: next_account_info(accounts_iter)?,
+            spl_token_program: next_account_info(accounts_iter)?,
+        };
+        if !a.referrer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(a)
+    }
+}
+
+/// Transfers a referrer's accrued `accumulated_referrer_fees` out of the market's quote vault
+/// to their SPL wallet, resetting the accrued amount to zero.
+pub fn process(_program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts)?;
+
+    let dex_state = DexState::get(accounts.market)?;
+    if dex_state.quote_vault != *accounts.quote_vault.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let signer_seeds: &[&[u8]] = &[&accounts.market.key.to_bytes(), &[dex_state.signer_nonce]];
+
+    let amount = {
+        let mut user_account_data = accounts.referrer_user_account.data.borrow_mut();
+        let user_account = UserAccount::from_buffer(&mut user_account_data)?;
+        if user_account.header.owner != *accounts.referrer.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let amount = user_account.header.accumulated_referrer_fees;
+        user_account.header.accumulated_referrer_fees = 0;
+        amount
+    };
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            accounts.spl_token_program.key,
+            accounts.quote_vault.key,
+            accounts.referrer_quote_wallet.key,
+            accounts.market_signer.key,
+            &[],
+            amount,
+        )?,
+        &[
+            accounts.quote_vault.clone(),
+            accounts.referrer_quote_wallet.clone(),
+            accounts.market_signer.clone(),
+            accounts.spl_token_program.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+use crate::state::{AccountTag, Order, UserAccountHeader, USER_ACCOUNT_HEADER_LEN};
+
+/// The number of orders' worth of capacity added to (or removed from) a user account per
+/// `grow_user_account`/`shrink_user_account` call.
+const GROW_CHUNK_ORDERS: usize = 213;
+
+/// The number of bytes a user account grows or shrinks by in a single call. Kept an exact
+/// multiple of `Order::LEN` so the orders tail always casts cleanly in
+/// `UserAccount::from_buffer`, and close to the maximum a single `realloc` call allows (10KiB).
+pub const GROW_CHUNK_SIZE: usize = GROW_CHUNK_ORDERS * Order::LEN;
+
+#[allow(missing_docs)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct Params {}
+
+struct Accounts<'a, 'b: 'a> {
+    user_account: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, 'b> {
+    fn parse(accounts: &'a [AccountInfo<'b>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let a = Self {
+            user_account: next_account_info(accounts_iter)?,
+            payer: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+        };
+        if !a.payer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(a)
+    }
+}
+
+/// Grows a user account's backing storage by one [`GROW_CHUNK_SIZE`] chunk, topping up the
+/// account's rent from `payer`. Market makers call this (ahead of time, or via a CPI-driven
+/// retry) once [`UserAccount::add_order`](crate::state::UserAccount::add_order) starts
+/// returning `DexError::UserAccountFull`, to raise the number of orders they can hold open
+/// simultaneously. `UserAccountHeader::number_of_orders` semantics are unaffected; only the
+/// backing capacity grows.
+pub fn process(_program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts)?;
+
+    {
+        let data = accounts.user_account.data.borrow();
+        let header: &UserAccountHeader = bytemuck::from_bytes(&data[0..USER_ACCOUNT_HEADER_LEN]);
+        if header.tag != AccountTag::UserAccount as u64 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    let new_size = accounts.user_account.data_len() + GROW_CHUNK_SIZE;
+    let rent = Rent::get()?;
+    let new_minimum_balance = rent.minimum_balance(new_size);
+    let lamports_diff = new_minimum_balance.saturating_sub(accounts.user_account.lamports());
+    if lamports_diff > 0 {
+        invoke(
+            &system_instruction::transfer(accounts.payer.key, accounts.user_account.key, lamports_diff),
+            &[
+                accounts.payer.clone(),
+                accounts.user_account.clone(),
+                accounts.system_program.clone(),
+            ],
+        )?;
+    }
+
+    accounts.user_account.realloc(new_size, false)?;
+
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use agnostic_orderbook::state::OrderSummary;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::DexError,
+    state::{DexState, FeeTier, Order, OrderType, Side, UserAccount},
+};
+
+#[allow(missing_docs)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct Params {
+    /// The side of the order (Bid or Ask)
+    pub side: Side,
+    /// The maximum quantity of base token to match or post
+    pub max_base_qty: u64,
+    /// The maximum quantity of quote token to match or post
+    pub max_quote_qty: u64,
+    /// The client-defined order id, stored alongside the raw order id for off-chain lookups
+    pub client_id: u128,
+    /// The order's time-in-force and crossing semantics
+    pub order_type: OrderType,
+    /// The unix timestamp after which this order is no longer valid. `0` means good-til-cancelled.
+    pub max_ts: i64,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    market: &'a AccountInfo<'b>,
+    orderbook: &'a AccountInfo<'b>,
+    event_queue: &'a AccountInfo<'b>,
+    bids: &'a AccountInfo<'b>,
+    asks: &'a AccountInfo<'b>,
+    base_vault: &'a AccountInfo<'b>,
+    quote_vault: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    user_account: &'a AccountInfo<'b>,
+    user_base_wallet: &'a AccountInfo<'b>,
+    user_quote_wallet: &'a AccountInfo<'b>,
+    discount_token_account: &'a AccountInfo<'b>,
+    spl_token_program: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, 'b> {
+    fn parse(accounts: &'a [AccountInfo<'b>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let a = Self {
+            market: next_account_info(accounts_iter)?,
+            orderbook: next_account_info(accounts_iter)?,
+            event_queue: next_account_info(accounts_iter)?,
+            bids: next_account_info(accounts_iter)?,
+            asks: next_account_info(accounts_iter)?,
+            base_vault: next_account_info(accounts_iter)?,
+            quote_vault: next_account_info(accounts_iter)?,
+            user: next_account_info(accounts_iter)?,
+            user_account: next_account_info(accounts_iter)?,
+            user_base_wallet: next_account_info(accounts_iter)?,
+            user_quote_wallet: next_account_info(accounts_iter)?,
+            discount_token_account: next_account_info(accounts_iter)?,
+            spl_token_program: next_account_info(accounts_iter)?,
+        };
+        if !a.user.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(a)
+    }
+}
+
+/// Posts a new order against the book, honoring the requested [`OrderType`]: `PostOnly` aborts
+/// rather than crossing the book, `FillOrKill` aborts unless `max_base_qty` is matched
+/// immediately, and an order carrying an already-elapsed `max_ts` is rejected before it is ever
+/// submitted to the matching engine. Matched proceeds and any resting remainder are credited to
+/// the caller's free/locked balances (see [`crate::processor::prune`] and
+/// [`crate::processor::grow_user_account`]); a separate settlement instruction is required to
+/// withdraw free balances to a token wallet.
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let Params {
+        side,
+        max_base_qty,
+        max_quote_qty,
+        client_id,
+        order_type,
+        max_ts,
+    } = params;
+
+    let now = Clock::get()?.unix_timestamp;
+    if max_ts != 0 && now > max_ts {
+        msg!("This order's expiry has already elapsed");
+        return Err(DexError::OrderExpired.into());
+    }
+
+    let accounts = Accounts::parse(accounts)?;
+
+    let mut dex_state = DexState::get(accounts.market)?;
+    if dex_state.orderbook != *accounts.orderbook.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if dex_state.base_vault != *accounts.base_vault.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if dex_state.quote_vault != *accounts.quote_vault.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let fee_tier = FeeTier::get(&dex_state, accounts.discount_token_account, accounts.user.key)?;
+
+    let post_only = order_type == OrderType::PostOnly;
+    let post_allowed = order_type != OrderType::FillOrKill;
+
+    let OrderSummary {
+        posted_order_id,
+        total_base_qty,
+        total_quote_qty,
+        ..
+    } = agnostic_orderbook::processor::new_order::process(
+        program_id,
+        agnostic_orderbook::processor::new_order::Params {
+            max_base_qty,
+            max_quote_qty,
+            side: side as u8,
+            post_only,
+            post_allowed,
+            self_trade_behavior: crate::state::SelfTradeBehavior::DecrementTake,
+            match_limit: u16::MAX as u64,
+        },
+        accounts.market,
+        accounts.orderbook,
+        accounts.event_queue,
+        accounts.bids,
+        accounts.asks,
+    )?;
+
+    if order_type == OrderType::PostOnly && posted_order_id.is_none() {
+        msg!("This PostOnly order would have crossed the book");
+        return Err(DexError::WouldCross.into());
+    }
+    if order_type == OrderType::FillOrKill && total_base_qty < max_base_qty {
+        msg!("This FillOrKill order could not be filled in its entirety");
+        return Err(DexError::CouldNotFill.into());
+    }
+
+    // Drain the oldest unprocessed fill so the oracle can be updated with that fill's own price
+    // rather than the whole instruction's volume-weighted average across every level it swept.
+    if total_base_qty > 0 {
+        let fill = agnostic_orderbook::processor::consume_events::process(
+            program_id,
+            agnostic_orderbook::processor::consume_events::Params { max_iterations: 1 },
+            accounts.market,
+            accounts.orderbook,
+            accounts.event_queue,
+        )?;
+        let fill_price_fp32 = match &fill {
+            Some(fill) => crate::utils::fp32_div(fill.quote_size, fill.base_size),
+            None => crate::utils::fp32_div(total_quote_qty, total_base_qty),
+        }
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+        dex_state.update_oracle(fill_price_fp32, now);
+    }
+    dex_state.base_volume = dex_state.base_volume.saturating_add(total_base_qty);
+    dex_state.quote_volume = dex_state.quote_volume.saturating_add(total_quote_qty);
+
+    let deposit_amount = {
+        let mut user_account_data = accounts.user_account.data.borrow_mut();
+        let mut user_account = UserAccount::from_buffer(&mut user_account_data)?;
+        if user_account.header.market != *accounts.market.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if user_account.header.owner != *accounts.user.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        match side {
+            Side::Bid => {
+                let fee = fee_tier.taker_fee(total_quote_qty);
+                dex_state.accumulated_fees += fee;
+                let quote_spent = total_quote_qty.saturating_add(fee);
+                let leftover_quote = max_quote_qty.saturating_sub(quote_spent);
+
+                user_account.header.base_token_free = user_account
+                    .header
+                    .base_token_free
+                    .saturating_add(total_base_qty);
+
+                if post_allowed && posted_order_id.is_some() {
+                    user_account.header.quote_token_locked = user_account
+                        .header
+                        .quote_token_locked
+                        .saturating_add(leftover_quote);
+                    user_account.add_order(Order::new(
+                        posted_order_id.unwrap(),
+                        client_id,
+                        order_type,
+                        max_ts,
+                    ))?;
+                } else {
+                    user_account.header.quote_token_free = user_account
+                        .header
+                        .quote_token_free
+                        .saturating_add(leftover_quote);
+                }
+
+                max_quote_qty
+            }
+            Side::Ask => {
+                let fee = fee_tier.taker_fee(total_quote_qty);
+                dex_state.accumulated_fees += fee;
+                let quote_out = total_quote_qty.saturating_sub(fee);
+                let leftover_base = max_base_qty.saturating_sub(total_base_qty);
+
+                user_account.header.quote_token_free = user_account
+                    .header
+                    .quote_token_free
+                    .saturating_add(quote_out);
+
+                if post_allowed && posted_order_id.is_some() {
+                    user_account.header.base_token_locked = user_account
+                        .header
+                        .base_token_locked
+                        .saturating_add(leftover_base);
+                    user_account.add_order(Order::new(
+                        posted_order_id.unwrap(),
+                        client_id,
+                        order_type,
+                        max_ts,
+                    ))?;
+                } else {
+                    user_account.header.base_token_free = user_account
+                        .header
+                        .base_token_free
+                        .saturating_add(leftover_base);
+                }
+
+                max_base_qty
+            }
+        }
+    };
+
+    match side {
+        Side::Bid => invoke(
+            &spl_token::instruction::transfer(
+                accounts.spl_token_program.key,
+                accounts.user_quote_wallet.key,
+                accounts.quote_vault.key,
+                accounts.user.key,
+                &[],
+                deposit_amount,
+            )?,
+            &[
+                accounts.user_quote_wallet.clone(),
+                accounts.quote_vault.clone(),
+                accounts.user.clone(),
+                accounts.spl_token_program.clone(),
+            ],
+        )?,
+        Side::Ask => invoke(
+            &spl_token::instruction::transfer(
+                accounts.spl_token_program.key,
+                accounts.user_base_wallet.key,
+                accounts.base_vault.key,
+                accounts.user.key,
+                &[],
+                deposit_amount,
+            )?,
+            &[
+                accounts.user_base_wallet.clone(),
+                accounts.base_vault.clone(),
+                accounts.user.clone(),
+                accounts.spl_token_program.clone(),
+            ],
+        )?,
+    }
+
+    Ok(())
+}
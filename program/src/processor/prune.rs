@@ -0,0 +1,106 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::state::{DexState, UserAccount};
+
+#[allow(missing_docs)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct Params {
+    /// The maximum number of expired orders to prune in this call, bounding compute usage.
+    pub max_iterations: u16,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    market: &'a AccountInfo<'b>,
+    orderbook: &'a AccountInfo<'b>,
+    event_queue: &'a AccountInfo<'b>,
+    bids: &'a AccountInfo<'b>,
+    asks: &'a AccountInfo<'b>,
+    user_account: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, 'b> {
+    fn parse(accounts: &'a [AccountInfo<'b>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        Ok(Self {
+            market: next_account_info(accounts_iter)?,
+            orderbook: next_account_info(accounts_iter)?,
+            event_queue: next_account_info(accounts_iter)?,
+            bids: next_account_info(accounts_iter)?,
+            asks: next_account_info(accounts_iter)?,
+            user_account: next_account_info(accounts_iter)?,
+        })
+    }
+}
+
+/// Permissionlessly prunes expired resting orders from a user account: cancels them on the
+/// orderbook and returns their locked base/quote amounts back to the user's free balances.
+/// Anyone (e.g. a keeper) may call this once an order's `max_ts` has elapsed.
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts)?;
+    {
+        let dex_state = DexState::get(accounts.market)?;
+        if dex_state.orderbook != *accounts.orderbook.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+    let now = Clock::get()?.unix_timestamp;
+
+    let mut user_account_data = accounts.user_account.data.borrow_mut();
+    let mut user_account = UserAccount::from_buffer(&mut user_account_data)?;
+    if user_account.header.market != *accounts.market.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut order_index = 0;
+    let mut pruned = 0u16;
+    while order_index < user_account.header.number_of_orders as usize
+        && pruned < params.max_iterations
+    {
+        let order = user_account.read_order(order_index)?;
+        if !order.is_expired(now) {
+            order_index += 1;
+            continue;
+        }
+
+        let freed = agnostic_orderbook::processor::cancel_order::process(
+            program_id,
+            agnostic_orderbook::processor::cancel_order::Params { order_id: order.id },
+            accounts.market,
+            accounts.orderbook,
+            accounts.event_queue,
+            accounts.bids,
+            accounts.asks,
+        )?;
+
+        user_account.header.base_token_locked = user_account
+            .header
+            .base_token_locked
+            .saturating_sub(freed.base_qty);
+        user_account.header.base_token_free = user_account
+            .header
+            .base_token_free
+            .saturating_add(freed.base_qty);
+        user_account.header.quote_token_locked = user_account
+            .header
+            .quote_token_locked
+            .saturating_sub(freed.quote_qty);
+        user_account.header.quote_token_free = user_account
+            .header
+            .quote_token_free
+            .saturating_add(freed.quote_qty);
+
+        // `remove_order` swaps the tail order into this slot, so the index is not advanced.
+        user_account.remove_order(order_index)?;
+        pruned += 1;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,314 @@
+use agnostic_orderbook::state::OrderSummary;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::DexError,
+    state::{DexState, FeeTier, Side, UserAccount},
+};
+
+#[allow(missing_docs)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct Params {
+    /// The side of the taker order (Bid or Ask)
+    pub side: Side,
+    /// The maximum quantity of base token to match
+    pub max_base_qty: u64,
+    /// The maximum quantity of quote token to match
+    pub max_quote_qty: u64,
+    /// The minimum quantity of base token the caller is willing to receive
+    pub min_base_out: u64,
+    /// The minimum quantity of quote token the caller is willing to receive
+    pub min_quote_out: u64,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    market: &'a AccountInfo<'b>,
+    orderbook: &'a AccountInfo<'b>,
+    event_queue: &'a AccountInfo<'b>,
+    bids: &'a AccountInfo<'b>,
+    asks: &'a AccountInfo<'b>,
+    base_vault: &'a AccountInfo<'b>,
+    quote_vault: &'a AccountInfo<'b>,
+    market_signer: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    user_account: &'a AccountInfo<'b>,
+    user_base_wallet: &'a AccountInfo<'b>,
+    user_quote_wallet: &'a AccountInfo<'b>,
+    discount_token_account: &'a AccountInfo<'b>,
+    spl_token_program: &'a AccountInfo<'b>,
+    /// The user's referrer's own user account, or the user's own account again if unreferred.
+    referrer_account: &'a AccountInfo<'b>,
+    /// The user account of the maker whose resting order the fill drained from the event queue
+    /// matched against, credited with the maker rebate for that fill's quote quantity.
+    maker_user_account: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, 'b> {
+    fn parse(accounts: &'a [AccountInfo<'b>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let a = Self {
+            market: next_account_info(accounts_iter)?,
+            orderbook: next_account_info(accounts_iter)?,
+            event_queue: next_account_info(accounts_iter)?,
+            bids: next_account_info(accounts_iter)?,
+            asks: next_account_info(accounts_iter)?,
+            base_vault: next_account_info(accounts_iter)?,
+            quote_vault: next_account_info(accounts_iter)?,
+            market_signer: next_account_info(accounts_iter)?,
+            user: next_account_info(accounts_iter)?,
+            user_account: next_account_info(accounts_iter)?,
+            user_base_wallet: next_account_info(accounts_iter)?,
+            user_quote_wallet: next_account_info(accounts_iter)?,
+            discount_token_account: next_account_info(accounts_iter)?,
+            spl_token_program: next_account_info(accounts_iter)?,
+            referrer_account: next_account_info(accounts_iter)?,
+            maker_user_account: next_account_info(accounts_iter)?,
+        };
+        if !a.user.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(a)
+    }
+}
+
+/// Runs an immediate-or-cancel taker order against the book and settles the net matched
+/// base/quote amounts directly to the caller's token wallets, bypassing the usual
+/// free-balance settlement step. No remainder is ever posted to the book.
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let Params {
+        side,
+        max_base_qty,
+        max_quote_qty,
+        min_base_out,
+        min_quote_out,
+    } = params;
+
+    let accounts = Accounts::parse(accounts)?;
+
+    let mut dex_state = DexState::get(accounts.market)?;
+    if dex_state.orderbook != *accounts.orderbook.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if dex_state.base_vault != *accounts.base_vault.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if dex_state.quote_vault != *accounts.quote_vault.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let fee_tier = FeeTier::get(&dex_state, accounts.discount_token_account, accounts.user.key)?;
+
+    // Match against the book as an IOC order: any unfilled remainder is cancelled, never posted.
+    let OrderSummary {
+        total_base_qty,
+        total_quote_qty,
+        ..
+    } = agnostic_orderbook::processor::new_order::process(
+        program_id,
+        agnostic_orderbook::processor::new_order::Params {
+            max_base_qty,
+            max_quote_qty,
+            side: side as u8,
+            post_only: false,
+            post_allowed: false,
+            self_trade_behavior: crate::state::SelfTradeBehavior::DecrementTake,
+            match_limit: u16::MAX as u64,
+        },
+        accounts.market,
+        accounts.orderbook,
+        accounts.event_queue,
+        accounts.bids,
+        accounts.asks,
+    )?;
+
+    let (base_out, quote_out) = match side {
+        Side::Bid => {
+            let fee = fee_tier.taker_fee(total_quote_qty);
+            dex_state.accumulated_fees += fee;
+            (total_base_qty, total_quote_qty.saturating_add(fee))
+        }
+        Side::Ask => {
+            let fee = fee_tier.taker_fee(total_quote_qty);
+            dex_state.accumulated_fees += fee;
+            (total_base_qty, total_quote_qty.saturating_sub(fee))
+        }
+    };
+
+    if side == Side::Bid && base_out < min_base_out {
+        msg!("Realized base output is below the specified slippage bound");
+        return Err(DexError::SlippageOutOfBounds.into());
+    }
+    if side == Side::Ask && quote_out < min_quote_out {
+        msg!("Realized quote output is below the specified slippage bound");
+        return Err(DexError::SlippageOutOfBounds.into());
+    }
+
+    dex_state.base_volume = dex_state.base_volume.saturating_add(total_base_qty);
+    dex_state.quote_volume = dex_state.quote_volume.saturating_add(total_quote_qty);
+
+    // Drain the oldest unprocessed fill so the oracle can be updated with that fill's own price
+    // rather than the whole instruction's volume-weighted average across every level it swept.
+    let fill = if total_base_qty > 0 {
+        agnostic_orderbook::processor::consume_events::process(
+            program_id,
+            agnostic_orderbook::processor::consume_events::Params { max_iterations: 1 },
+            accounts.market,
+            accounts.orderbook,
+            accounts.event_queue,
+        )?
+    } else {
+        None
+    };
+
+    if total_base_qty > 0 {
+        let fill_price_fp32 = match &fill {
+            Some(fill) => crate::utils::fp32_div(fill.quote_size, fill.base_size),
+            None => crate::utils::fp32_div(total_quote_qty, total_base_qty),
+        }
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+        dex_state.update_oracle(fill_price_fp32, solana_program::clock::Clock::get()?.unix_timestamp);
+    }
+
+    let referrer = {
+        let mut user_account_data = accounts.user_account.data.borrow_mut();
+        let user_account = UserAccount::from_buffer(&mut user_account_data)?;
+        if user_account.header.market != *accounts.market.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if user_account.header.owner != *accounts.user.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        user_account.header.accumulated_taker_base_volume = user_account
+            .header
+            .accumulated_taker_base_volume
+            .saturating_add(total_base_qty);
+        user_account.header.accumulated_taker_quote_volume = user_account
+            .header
+            .accumulated_taker_quote_volume
+            .saturating_add(total_quote_qty);
+        user_account.header.referrer
+    };
+
+    if referrer != Pubkey::default() {
+        let mut referrer_account_data = accounts.referrer_account.data.borrow_mut();
+        let referrer_account = UserAccount::from_buffer(&mut referrer_account_data)?;
+        if referrer_account.header.owner == referrer {
+            let referral_fee = fee_tier.referral_fee(total_quote_qty);
+            dex_state.accumulated_fees = dex_state.accumulated_fees.saturating_sub(referral_fee);
+            referrer_account.header.accumulated_referrer_fees = referrer_account
+                .header
+                .accumulated_referrer_fees
+                .saturating_add(referral_fee);
+        }
+    }
+
+    // Bind the rebate to the fill drained above so it can't be credited to an unrelated account.
+    if let Some(fill) = &fill {
+        let mut maker_account_data = accounts.maker_user_account.data.borrow_mut();
+        let maker_account = UserAccount::from_buffer(&mut maker_account_data)?;
+        if maker_account.header.market != *accounts.market.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // Errors out unless `maker_user_account` actually holds the order this fill matched
+        // against, closing off crediting an unrelated account.
+        maker_account.find_order_index(fill.maker_order_id)?;
+
+        // The maker's own fee tier isn't available here (no discount token account is passed
+        // for them), so the rebate is computed at the base tier.
+        let rebate = maker_account
+            .header
+            .credit_maker_rebate(fill.quote_size, FeeTier::Base);
+        dex_state.accumulated_fees = dex_state.accumulated_fees.saturating_sub(rebate);
+    }
+
+    let signer_seeds: &[&[u8]] = &[
+        &accounts.market.key.to_bytes(),
+        &[dex_state.signer_nonce],
+    ];
+
+    match side {
+        Side::Bid => {
+            // The taker pays quote (including fee) and receives base.
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    accounts.spl_token_program.key,
+                    accounts.user_quote_wallet.key,
+                    accounts.quote_vault.key,
+                    accounts.user.key,
+                    &[],
+                    quote_out,
+                )?,
+                &[
+                    accounts.user_quote_wallet.clone(),
+                    accounts.quote_vault.clone(),
+                    accounts.user.clone(),
+                    accounts.spl_token_program.clone(),
+                ],
+                &[],
+            )?;
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    accounts.spl_token_program.key,
+                    accounts.base_vault.key,
+                    accounts.user_base_wallet.key,
+                    accounts.market_signer.key,
+                    &[],
+                    base_out,
+                )?,
+                &[
+                    accounts.base_vault.clone(),
+                    accounts.user_base_wallet.clone(),
+                    accounts.market_signer.clone(),
+                    accounts.spl_token_program.clone(),
+                ],
+                &[signer_seeds],
+            )?;
+        }
+        Side::Ask => {
+            // The taker pays base and receives quote (net of fee).
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    accounts.spl_token_program.key,
+                    accounts.user_base_wallet.key,
+                    accounts.base_vault.key,
+                    accounts.user.key,
+                    &[],
+                    base_out,
+                )?,
+                &[
+                    accounts.user_base_wallet.clone(),
+                    accounts.base_vault.clone(),
+                    accounts.user.clone(),
+                    accounts.spl_token_program.clone(),
+                ],
+                &[],
+            )?;
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    accounts.spl_token_program.key,
+                    accounts.quote_vault.key,
+                    accounts.user_quote_wallet.key,
+                    accounts.market_signer.key,
+                    &[],
+                    quote_out,
+                )?,
+                &[
+                    accounts.quote_vault.clone(),
+                    accounts.user_quote_wallet.clone(),
+                    accounts.market_signer.clone(),
+                    accounts.spl_token_program.clone(),
+                ],
+                &[signer_seeds],
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::DexError,
+    processor::grow_user_account::GROW_CHUNK_SIZE,
+    state::{Order, UserAccount, USER_ACCOUNT_HEADER_LEN},
+};
+
+#[allow(missing_docs)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct Params {}
+
+struct Accounts<'a, 'b: 'a> {
+    user_account: &'a AccountInfo<'b>,
+    owner: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, 'b> {
+    fn parse(accounts: &'a [AccountInfo<'b>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let a = Self {
+            user_account: next_account_info(accounts_iter)?,
+            owner: next_account_info(accounts_iter)?,
+        };
+        if !a.owner.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(a)
+    }
+}
+
+/// Reclaims rent from a user account's tail by shrinking its backing storage by one
+/// [`GROW_CHUNK_SIZE`] chunk, once `UserAccountHeader::number_of_orders` is low enough that
+/// the freed chunk holds no live orders. Refuses to shrink below the live order count.
+pub fn process(_program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts)?;
+
+    let current_size = accounts.user_account.data_len();
+    let new_size = current_size
+        .checked_sub(GROW_CHUNK_SIZE)
+        .ok_or(DexError::CannotShrinkUserAccount)?;
+    if new_size < USER_ACCOUNT_HEADER_LEN {
+        return Err(DexError::CannotShrinkUserAccount.into());
+    }
+    let new_order_capacity = (new_size - USER_ACCOUNT_HEADER_LEN) / Order::LEN;
+
+    {
+        let mut data = accounts.user_account.data.borrow_mut();
+        let user_account = UserAccount::from_buffer(&mut data)?;
+        if user_account.header.owner != *accounts.owner.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if (user_account.header.number_of_orders as usize) > new_order_capacity {
+            return Err(DexError::CannotShrinkUserAccount.into());
+        }
+    }
+
+    let rent = Rent::get()?;
+    let new_minimum_balance = rent.minimum_balance(new_size);
+    let lamports_diff = accounts
+        .user_account
+        .lamports()
+        .saturating_sub(new_minimum_balance);
+
+    accounts.user_account.realloc(new_size, false)?;
+
+    **accounts.owner.lamports.borrow_mut() += lamports_diff;
+    **accounts.user_account.lamports.borrow_mut() -= lamports_diff;
+
+    Ok(())
+}
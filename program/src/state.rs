@@ -1,6 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use bytemuck::{try_cast_slice_mut, try_from_bytes_mut, Pod, Zeroable};
 use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
 use solana_program::{
     account_info::AccountInfo, msg, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
 };
@@ -22,7 +23,9 @@ pub enum AccountTag {
     Closed,
 }
 
-#[derive(Clone, Copy, PartialEq, FromPrimitive, ToPrimitive)]
+#[derive(
+    Clone, Copy, PartialEq, FromPrimitive, ToPrimitive, BorshDeserialize, BorshSerialize,
+)]
 #[repr(u8)]
 #[allow(missing_docs)]
 pub enum Side {
@@ -80,12 +83,19 @@ pub struct DexState {
     pub base_currency_multiplier: u64,
     /// The quote currency multiplier
     pub quote_currency_multiplier: u64,
+    /// The TWAP oracle's cumulative price-time accumulator. Diffing two snapshots of this
+    /// field taken `Δt` apart yields a time-weighted average price over that interval.
+    pub price_cumulative: u128,
+    /// The fp32 price of the last fill matched on this market.
+    pub last_price_fp32: u64,
+    /// The runtime clock timestamp at which the oracle was last updated.
+    pub last_oracle_ts: i64,
     /// The signer nonce is necessary for the market to perform as a signing entity
     pub signer_nonce: u8,
     /// Fee type (e.g. default or stable)
     pub fee_type: u8,
     /// Padding
-    pub _padding: [u8; 6],
+    pub _padding: [u8; 14],
 }
 
 /// Size in bytes of the dex state object
@@ -108,6 +118,42 @@ impl DexState {
         });
         a
     }
+
+    /// Records a fill's price into the TWAP oracle accumulator. Must be called at the end of
+    /// every match with the last fill's fp32 price and the current clock timestamp.
+    ///
+    /// Updates with `now <= last_oracle_ts` leave the accumulator and `last_oracle_ts` unchanged
+    /// and only refresh `last_price_fp32`, so the accumulator only ever advances with elapsed
+    /// time, and accumulation uses saturating math so a market left idle
+    /// for a long stretch cannot overflow the u128 accumulator.
+    pub fn update_oracle(&mut self, fill_price_fp32: u64, now: i64) {
+        if now > self.last_oracle_ts {
+            let elapsed = (now - self.last_oracle_ts) as u128;
+            self.price_cumulative = self
+                .price_cumulative
+                .saturating_add((self.last_price_fp32 as u128).saturating_mul(elapsed));
+            self.last_oracle_ts = now;
+        }
+        self.last_price_fp32 = fill_price_fp32;
+    }
+
+    /// Returns the oracle's current cumulative price snapshot. Two snapshots taken `Δt` apart
+    /// yield a TWAP over that interval: `(cum2 - cum1) / (t2 - t1)`.
+    pub fn oracle_snapshot(&self) -> PriceOracleSnapshot {
+        PriceOracleSnapshot {
+            price_cumulative: self.price_cumulative,
+            last_oracle_ts: self.last_oracle_ts,
+        }
+    }
+}
+
+/// A point-in-time snapshot of [`DexState`]'s TWAP oracle accumulator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceOracleSnapshot {
+    /// The cumulative price-time accumulator at the time of the snapshot.
+    pub price_cumulative: u128,
+    /// The runtime clock timestamp at the time of the snapshot.
+    pub last_oracle_ts: i64,
 }
 
 /// This header describes a user account's state
@@ -144,6 +190,27 @@ pub struct UserAccountHeader {
     _padding: u32,
     /// The user account's number of active orders.
     pub number_of_orders: u32,
+    /// The wallet that referred this user, set once at account creation. `Pubkey::default()`
+    /// means the user has no referrer.
+    pub referrer: Pubkey,
+    /// The referral fees this account's owner has accrued as the referrer of other users,
+    /// pending a `claim_referral_fees` transfer out of the quote vault, which resets it to zero.
+    pub accumulated_referrer_fees: u64,
+}
+
+/// Describes an order's time-in-force and crossing semantics.
+#[derive(
+    Clone, Copy, PartialEq, FromPrimitive, ToPrimitive, BorshDeserialize, BorshSerialize,
+)]
+#[repr(u8)]
+#[allow(missing_docs)]
+pub enum OrderType {
+    /// A regular resting order: may cross the book and rests for any remainder.
+    Limit,
+    /// Aborts instead of crossing the book; only ever rests.
+    PostOnly,
+    /// Aborts unless the full requested size is matched immediately; never rests.
+    FillOrKill,
 }
 
 /// Represents and order in the user account. The client id offers an alias which can be used off-chain to map custom ids to an actual order id.
@@ -154,11 +221,39 @@ pub struct Order {
     pub id: u128,
     /// The client-defined order id. Care should be taken off-chain to only create new orders with new client_ids.
     pub client_id: u128,
+    /// The unix timestamp after which this order is no longer valid and may be pruned from the
+    /// book by anyone. A value of `0` means good-til-cancelled.
+    pub max_ts: i64,
+    /// The packed [`OrderType`] tag of this order.
+    order_type: u8,
+    /// Padding to keep the struct's size a multiple of its 16 byte alignment.
+    _padding: [u8; 7],
 }
 
 impl Order {
     /// The length in bytes of the order's binary representation
     pub const LEN: usize = std::mem::size_of::<Self>();
+
+    #[allow(missing_docs)]
+    pub fn new(id: u128, client_id: u128, order_type: OrderType, max_ts: i64) -> Self {
+        Self {
+            id,
+            client_id,
+            max_ts,
+            order_type: order_type as u8,
+            _padding: [0; 7],
+        }
+    }
+
+    /// Unpacks this order's [`OrderType`] tag.
+    pub fn order_type(&self) -> OrderType {
+        OrderType::from_u8(self.order_type).unwrap()
+    }
+
+    /// Returns `true` if this order carries an expiry (`max_ts != 0`) which has elapsed as of `now`.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.max_ts != 0 && now > self.max_ts
+    }
 }
 
 #[allow(missing_docs)]
@@ -168,10 +263,10 @@ pub struct UserAccount<'a> {
 }
 
 /// Size in bytes of the user account header object
-pub const USER_ACCOUNT_HEADER_LEN: usize = 152;
+pub const USER_ACCOUNT_HEADER_LEN: usize = 192;
 
 impl UserAccountHeader {
-    pub(crate) fn new(market: &Pubkey, owner: &Pubkey) -> Self {
+    pub(crate) fn new(market: &Pubkey, owner: &Pubkey, referrer: &Pubkey) -> Self {
         Self {
             tag: AccountTag::UserAccount as u64,
             market: *market,
@@ -187,6 +282,8 @@ impl UserAccountHeader {
             accumulated_maker_base_volume: 0,
             accumulated_taker_quote_volume: 0,
             accumulated_taker_base_volume: 0,
+            referrer: *referrer,
+            accumulated_referrer_fees: 0,
         }
     }
 }
@@ -211,6 +308,19 @@ impl<'a> UserAccount<'a> {
     }
 }
 
+impl UserAccountHeader {
+    /// Credits this maker with their rebate for a fill of `quote_qty`, funded out of the
+    /// taker fee collected for the same fill. Returns the rebate amount, which the caller
+    /// must subtract from `DexState::accumulated_fees` so the market never pays out more
+    /// than it took in.
+    pub fn credit_maker_rebate(&mut self, quote_qty: u64, maker_fee_tier: FeeTier) -> u64 {
+        let rebate = maker_fee_tier.maker_rebate(quote_qty);
+        self.quote_token_free = self.quote_token_free.saturating_add(rebate);
+        self.accumulated_rebates = self.accumulated_rebates.saturating_add(rebate);
+        rebate
+    }
+}
+
 impl<'a> UserAccount<'a> {
     #[allow(missing_docs)]
     pub fn read_order(&self, order_index: usize) -> Result<Order, DexError> {
@@ -362,11 +472,20 @@ impl FeeTier {
     }
 
     pub fn maker_rate(self) -> u64 {
-        0
+        match self {
+            FeeTier::Base => (4 << 32) / 100_000,
+            FeeTier::Srm2 => (6 << 32) / 100_000,
+            FeeTier::Srm3 => (8 << 32) / 100_000,
+            FeeTier::Srm4 => (10 << 32) / 100_000,
+            FeeTier::Srm5 => (12 << 32) / 100_000,
+            FeeTier::Srm6 => (14 << 32) / 100_000,
+            FeeTier::MSrm => (16 << 32) / 100_000,
+            FeeTier::Stable => (2 << 32) / 100_000,
+        }
     }
 
-    pub fn maker_rebate(self, _quote_qty: u64) -> u64 {
-        0
+    pub fn maker_rebate(self, quote_qty: u64) -> u64 {
+        fp32_mul(quote_qty, self.maker_rate()).unwrap()
     }
 
     pub fn remove_taker_fee(self, quote_qty: u64) -> u64 {
@@ -0,0 +1,17 @@
+//! Fixed point (fp32) arithmetic helpers used throughout the program for fee and price math.
+
+/// Represents `1.0` in 32.32 fixed point notation.
+pub const FP_32_ONE: u64 = 1 << 32;
+
+/// Multiplies an integer quantity by a fp32 rate, returning `None` on overflow.
+pub fn fp32_mul(amount: u64, fp32_rate: u64) -> Option<u64> {
+    u64::try_from((amount as u128 * fp32_rate as u128) >> 32).ok()
+}
+
+/// Divides an integer quantity by a fp32 rate, returning `None` on overflow or division by zero.
+pub fn fp32_div(amount: u64, fp32_rate: u64) -> Option<u64> {
+    if fp32_rate == 0 {
+        return None;
+    }
+    u64::try_from(((amount as u128) << 32) / fp32_rate as u128).ok()
+}